@@ -1,6 +1,9 @@
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use lms_hss::{get_lmots_parameters, serialize_public_key};
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use std::collections::HashSet;
 use std::io::Write;
 
 /*
@@ -22,13 +25,169 @@ struct Args {
     #[arg(long)]
     tests: u32,
 
+    /// Number of negative (mutated, `test_passed == false`) vectors to emit.
+    #[arg(long, default_value_t = 0)]
+    negative_tests: u32,
+
+    /// Alternative to `--negative-tests`: emit `round(ratio * positives)` negatives.
+    #[arg(long)]
+    negative_ratio: Option<f64>,
+
+    /// Seed (decimal or `0x`-prefixed hex `u64`) for the `q` leaf selection ONLY.
+    /// Key/tree material and signatures are drawn by `lms_hss`, which takes no rng,
+    /// so a corpus is NOT byte-for-byte reproducible from this seed — only which
+    /// leaves are chosen is deterministic. When omitted a random seed is drawn.
+    #[arg(long)]
+    seed: Option<String>,
+
+    /// Number of HSS levels (1-8). `1` produces a single LMS tree exactly as before;
+    /// `L > 1` chains `L` trees where each parent signs the child tree's public key.
+    #[arg(long, default_value_t = 1)]
+    levels: u8,
+
+    /// Verify every signed (positive) vector against the independent RustCrypto `lms`
+    /// oracle before emitting; exit non-zero on any disagreement. The mutated
+    /// negative vectors are not run through either verifier.
+    #[arg(long, default_value_t = false)]
+    cross_check: bool,
+
+    /// Output format: `rust` (caliptra in-tree source) or `kat` (portable KAT records).
+    #[arg(long, value_enum, default_value_t = Format::Rust)]
+    format: Format,
+
     #[arg(long)]
     filename: String,
 }
 
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Format {
+    Rust,
+    Kat,
+}
+
 struct LmsTest {
     test_passed: bool,
     signature: Vec<u8>,
+    label: String,
+}
+
+/// A single targeted corruption applied to a serialized LMS signature to drive
+/// one of `verify_lms_signature_generic`'s failure paths. Each variant flips or
+/// rewrites exactly one field so the resulting vector exercises a known error.
+#[derive(Clone, Copy, Debug)]
+enum Corruption {
+    /// Flip a byte inside the LM-OTS `C` randomizer.
+    OtsRandomizer,
+    /// Flip a byte inside one of the `p` LM-OTS `y[i]` hash chains.
+    OtsHashChain,
+    /// Corrupt a node in the LMS authentication path.
+    AuthPath,
+    /// Overwrite the leading LM-OTS type identifier word with a wrong-but-valid code.
+    TypeIdentifier,
+    /// Move the `q` leaf index to a different in-range value.
+    LeafIndex,
+    /// Truncate the signature by a few trailing bytes.
+    Truncate,
+}
+
+impl Corruption {
+    const ALL: [Corruption; 6] = [
+        Corruption::OtsRandomizer,
+        Corruption::OtsHashChain,
+        Corruption::AuthPath,
+        Corruption::TypeIdentifier,
+        Corruption::LeafIndex,
+        Corruption::Truncate,
+    ];
+
+    /// A human-readable note on the failure this mutation is expected to drive.
+    /// Emitted as a comment only: the generated harness asserts that a negative
+    /// vector fails verification, not which specific error code it produces.
+    fn expected(&self) -> &'static str {
+        match self {
+            // A parse/align failure: the byte length no longer matches a signature.
+            Corruption::Truncate => "Err(..) (deserialization failure)",
+            // Everything else still deserializes but must fail verification.
+            _ => "LmsResult::SigVerifyFailed",
+        }
+    }
+}
+
+/// Layout of a serialized LMS signature (RFC 8554 §5.4), in bytes:
+///   q (4) || lmots_type (4) || C (n) || y[0..p] (p*n) || lms_type (4) || path[0..h] (h*n)
+/// Returns `None` when `sig` is too short to hold the targeted field.
+fn corrupt_signature(sig: &[u8], kind: Corruption, n: usize, p: usize, h: usize) -> Option<Vec<u8>> {
+    let c_start = 8;
+    let y_start = c_start + n;
+    let lms_type_start = y_start + p * n;
+    let path_start = lms_type_start + 4;
+    let expected_len = path_start + h * n;
+    if sig.len() < expected_len {
+        return None;
+    }
+    let mut out = sig.to_vec();
+    match kind {
+        Corruption::OtsRandomizer => out[c_start] ^= 0xff,
+        Corruption::OtsHashChain => out[y_start] ^= 0xff,
+        Corruption::AuthPath => out[path_start] ^= 0xff,
+        Corruption::TypeIdentifier => {
+            // The LM-OTS type is a u32 in [1, 8]; bump it to a different valid code.
+            let code = u32::from_be_bytes([out[4], out[5], out[6], out[7]]);
+            let other = if code == 8 { code - 1 } else { code + 1 };
+            out[4..8].copy_from_slice(&other.to_be_bytes());
+        }
+        Corruption::LeafIndex => {
+            let max = 1u32 << h;
+            let q = u32::from_be_bytes([out[0], out[1], out[2], out[3]]);
+            let other = (q + 1) % max;
+            out[0..4].copy_from_slice(&other.to_be_bytes());
+        }
+        Corruption::Truncate => out.truncate(out.len() - 4),
+    }
+    Some(out)
+}
+
+/// Build up to `count` negative vectors by cycling every base signature through
+/// each `Corruption` in turn, labelling each with the failure it should drive.
+fn generate_negative_tests(
+    positives: &[LmsTest],
+    count: usize,
+    n: usize,
+    p: usize,
+    h: usize,
+) -> Vec<LmsTest> {
+    let mut out = Vec::with_capacity(count);
+    let mut seen: HashSet<Vec<u8>> = HashSet::new();
+    if positives.is_empty() {
+        return out;
+    }
+    // Each (base, corruption) pair yields at most one distinct vector, so once a
+    // full pass over every pair adds nothing new the corpus is exhausted. This
+    // both terminates when `count` outpaces the available mutations and keeps the
+    // emitted negatives unique.
+    loop {
+        let before = out.len();
+        for base in positives {
+            for kind in Corruption::ALL {
+                if out.len() == count {
+                    return out;
+                }
+                if let Some(signature) = corrupt_signature(&base.signature, kind, n, p, h) {
+                    if seen.insert(signature.clone()) {
+                        out.push(LmsTest {
+                            test_passed: false,
+                            signature,
+                            label: format!("{:?}: expects {}", kind, kind.expected()),
+                        });
+                    }
+                }
+            }
+        }
+        if out.len() == before {
+            break;
+        }
+    }
+    out
 }
 
 const BOILERPLATE_1: &str = r#"/*++
@@ -60,6 +219,12 @@ fn test_lms_random_suite() {
 
 const BOILER_PLATE2: &str = r#"
         assert!(head.is_empty());
+        if thing2.is_empty() {
+            // a signature that doesn't deserialize (e.g. a truncated one) can only ever
+            // be a negative vector, and there is nothing to hand to the verifier
+            assert!(!t.test_passed);
+            continue;
+        }
         let lms_sig = thing2[0];
         let verify_result = Lms::default().verify_lms_signature_generic(
             &mut sha256,
@@ -87,7 +252,301 @@ test_suite! {
 }
 "#;
 
-fn write_test_file(
+const HSS_BOILERPLATE_1: &str = r#"/*++
+
+Licensed under the Apache-2.0 license.
+
+Abstract:
+
+    File contains test cases for HSS (multi-level LMS) signature verification. This file is machine generated.
+
+--*/
+
+#![no_std]
+#![no_main]
+
+use caliptra_drivers::{Lms, LmsResult, Sha256};
+use caliptra_lms_types::{HssPublicKey, HssSignature};
+use caliptra_registers::sha256::Sha256Reg;
+use caliptra_test_harness::test_suite;
+
+struct HssTest<'a> {
+    test_passed: bool,
+    signature: &'a [u8],
+}
+
+fn test_hss_random_suite() {
+    let mut sha256 = unsafe { Sha256::new(Sha256Reg::new()) };
+    "#;
+
+const HSS_BOILER_PLATE2: &str = r#"
+        assert!(head.is_empty());
+        if thing2.is_empty() {
+            // a signature that doesn't deserialize (e.g. a truncated one) can only ever
+            // be a negative vector, and there is nothing to hand to the verifier
+            assert!(!t.test_passed);
+            continue;
+        }
+        let hss_sig = thing2[0];
+        let verify_result = Lms::default().verify_hss_signature_generic(
+            &mut sha256,
+            &MESSAGE,
+            &hss_public_key,
+            &hss_sig,
+        );
+        if t.test_passed {
+            // if the test is supposed to pass then we better have no errors and a successful verification
+            let result = verify_result.unwrap();
+            assert_eq!(result, LmsResult::Success)
+        } else {
+            // if the test is supposed to fail it could be for a number of reasons that could raise a variety of errors
+            // if the verification didn't error, then extract the LMS result and ensure it is a failed verification
+            if verify_result.is_ok() {
+                let result = verify_result.unwrap();
+                assert_eq!(result, LmsResult::SigVerifyFailed)
+            }
+        }
+    }
+}
+
+test_suite! {
+    test_hss_random_suite,
+}
+"#;
+
+/// Build a full HSS corpus: a chain of `levels` LMS trees where each parent signs
+/// its child tree's public key (RFC 8554 §6). Returns the serialized HSS public key
+/// (`u32str(L) || root_lms_pubkey`) and one serialized HSS signature per chosen leaf
+/// (`u32str(L-1) || signed_pub_key[0..L-1] || bottom_lms_signature`).
+fn build_hss_vectors<const N: usize>(
+    levels: u8,
+    lms_type: &lms_hss::LmsAlgorithmType,
+    ots_type: &lms_hss::LmotsAlgorithmType,
+    message: &[u8],
+    chosen_qs: &[u32],
+) -> (Vec<u8>, Vec<Vec<u8>>) {
+    let levels = levels as usize;
+    // Index 0 is the root (top) tree; the last entry signs the actual message.
+    let mut pubs = Vec::with_capacity(levels);
+    let mut trees = Vec::with_capacity(levels);
+    for _ in 0..levels {
+        let (pk, tree) = lms_hss::create_lms_tree::<N>(lms_type, ots_type).unwrap();
+        pubs.push(pk);
+        trees.push(tree);
+    }
+
+    // The signed public keys are fixed for the whole corpus; only the bottom tree's
+    // message signature varies per test, so build this prefix once.
+    let mut prefix = Vec::new();
+    prefix.extend_from_slice(&((levels as u32) - 1).to_be_bytes());
+    for i in 0..levels - 1 {
+        let child_pub = serialize_public_key(&pubs[i + 1]);
+        let q = trees[i].q;
+        let sig = lms_hss::lms_sign_message(
+            ots_type,
+            lms_type,
+            &child_pub,
+            &trees[i].private_keys[q as usize].clone(),
+            q,
+            &trees[i],
+        )
+        .unwrap();
+        prefix.extend_from_slice(&lms_hss::serialize_signature(&sig));
+        prefix.extend_from_slice(&child_pub);
+    }
+
+    let mut hss_public_key = Vec::new();
+    hss_public_key.extend_from_slice(&(levels as u32).to_be_bytes());
+    hss_public_key.extend_from_slice(&serialize_public_key(&pubs[0]));
+
+    let bottom = levels - 1;
+    let mut signatures = Vec::with_capacity(chosen_qs.len());
+    for offset_q in chosen_qs {
+        let the_q_to_use = trees[bottom].q + offset_q;
+        let lms_sig = lms_hss::lms_sign_message(
+            ots_type,
+            lms_type,
+            message,
+            &trees[bottom].private_keys[the_q_to_use as usize].clone(),
+            the_q_to_use,
+            &trees[bottom],
+        )
+        .unwrap();
+        let mut hss_sig = prefix.clone();
+        hss_sig.extend_from_slice(&lms_hss::serialize_signature(&lms_sig));
+        signatures.push(hss_sig);
+    }
+    (hss_public_key, signatures)
+}
+
+/// Verify `(public_key, message, signature)` with the RustCrypto `lms` crate,
+/// reconstructing its `VerifyingKey`/`Signature` straight from the serialized
+/// bytes. `Mode` must match the LMS/LM-OTS types the vector was generated with.
+///
+/// Gated behind the `cross-check` feature so the default build does not pull in
+/// the `lms`/`signature` crates; enable it with `--features cross-check`.
+#[cfg(feature = "cross-check")]
+fn rustcrypto_verifies<Mode: lms::lms::LmsMode>(
+    public_key: &[u8],
+    message: &[u8],
+    signature: &[u8],
+) -> bool {
+    use signature::Verifier;
+    let verifying_key = match lms::VerifyingKey::<Mode>::try_from(public_key) {
+        Ok(vk) => vk,
+        Err(_) => return false,
+    };
+    let sig = match lms::Signature::<Mode>::try_from(signature) {
+        Ok(sig) => sig,
+        Err(_) => return false,
+    };
+    verifying_key.verify(message, &sig).is_ok()
+}
+
+/// Dispatch the RustCrypto oracle over the concrete mode named by the CLI types.
+/// Only the SHA-256/N=32 modes RustCrypto ships are wired; other parameter sets
+/// return `None`, signalling "no independent oracle available".
+#[cfg(feature = "cross-check")]
+fn rustcrypto_cross_check(
+    lms_type: &lms_hss::LmsAlgorithmType,
+    ots_type: &lms_hss::LmotsAlgorithmType,
+    public_key: &[u8],
+    message: &[u8],
+    signature: &[u8],
+) -> Option<bool> {
+    use lms::lms::{LmsSha256N32H10, LmsSha256N32H15, LmsSha256N32H20, LmsSha256N32H5};
+    use lms::ots::{LmsOtsSha256N32W1, LmsOtsSha256N32W2, LmsOtsSha256N32W4, LmsOtsSha256N32W8};
+    use lms_hss::LmotsAlgorithmType::*;
+    use lms_hss::LmsAlgorithmType::*;
+
+    macro_rules! check {
+        ($tree:ident, $ots:ty) => {
+            Some(rustcrypto_verifies::<$tree<$ots>>(public_key, message, signature))
+        };
+    }
+    match (lms_type, ots_type) {
+        (LmsSha256N32H5, LmotsSha256N32W1) => check!(LmsSha256N32H5, LmsOtsSha256N32W1),
+        (LmsSha256N32H5, LmotsSha256N32W2) => check!(LmsSha256N32H5, LmsOtsSha256N32W2),
+        (LmsSha256N32H5, LmotsSha256N32W4) => check!(LmsSha256N32H5, LmsOtsSha256N32W4),
+        (LmsSha256N32H5, LmotsSha256N32W8) => check!(LmsSha256N32H5, LmsOtsSha256N32W8),
+        (LmsSha256N32H10, LmotsSha256N32W1) => check!(LmsSha256N32H10, LmsOtsSha256N32W1),
+        (LmsSha256N32H10, LmotsSha256N32W2) => check!(LmsSha256N32H10, LmsOtsSha256N32W2),
+        (LmsSha256N32H10, LmotsSha256N32W4) => check!(LmsSha256N32H10, LmsOtsSha256N32W4),
+        (LmsSha256N32H10, LmotsSha256N32W8) => check!(LmsSha256N32H10, LmsOtsSha256N32W8),
+        (LmsSha256N32H15, LmotsSha256N32W1) => check!(LmsSha256N32H15, LmsOtsSha256N32W1),
+        (LmsSha256N32H15, LmotsSha256N32W2) => check!(LmsSha256N32H15, LmsOtsSha256N32W2),
+        (LmsSha256N32H15, LmotsSha256N32W4) => check!(LmsSha256N32H15, LmsOtsSha256N32W4),
+        (LmsSha256N32H15, LmotsSha256N32W8) => check!(LmsSha256N32H15, LmsOtsSha256N32W8),
+        (LmsSha256N32H20, LmotsSha256N32W1) => check!(LmsSha256N32H20, LmsOtsSha256N32W1),
+        (LmsSha256N32H20, LmotsSha256N32W2) => check!(LmsSha256N32H20, LmsOtsSha256N32W2),
+        (LmsSha256N32H20, LmotsSha256N32W4) => check!(LmsSha256N32H20, LmsOtsSha256N32W4),
+        (LmsSha256N32H20, LmotsSha256N32W8) => check!(LmsSha256N32H20, LmsOtsSha256N32W8),
+        _ => None,
+    }
+}
+
+/// Stub used when the crate is built without the `cross-check` feature: there is
+/// no oracle, so every lookup reports "unavailable" and `main` refuses to emit.
+#[cfg(not(feature = "cross-check"))]
+fn rustcrypto_cross_check(
+    _lms_type: &lms_hss::LmsAlgorithmType,
+    _ots_type: &lms_hss::LmotsAlgorithmType,
+    _public_key: &[u8],
+    _message: &[u8],
+    _signature: &[u8],
+) -> Option<bool> {
+    None
+}
+
+/// Everything an emitter needs to render one corpus to disk.
+struct EmitContext<'a> {
+    message: &'a [u8],
+    public_key: &'a [u8],
+    tests: &'a [LmsTest],
+    n: u8,
+    w: u8,
+    p: u16,
+    height: u8,
+    /// HSS level count; `1` is a plain single LMS tree.
+    levels: u8,
+    /// Human-readable algorithm identifier, e.g. `LmsSha256N32H5 / LmotsSha256N32W8`.
+    algorithm: &'a str,
+}
+
+/// Renders a generated corpus into a concrete on-disk format.
+trait TestEmitter {
+    fn emit(&self, filename: &str, ctx: &EmitContext);
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    let mut s = String::with_capacity(bytes.len() * 2);
+    for b in bytes {
+        s.push_str(&format!("{:02x}", b));
+    }
+    s
+}
+
+/// Emits a `#![no_std]` Rust source file wired to caliptra's `Lms`/`LmsSignature` types,
+/// or to its `HssPublicKey`/`HssSignature` types when `levels > 1`.
+struct RustEmitter;
+
+impl TestEmitter for RustEmitter {
+    fn emit(&self, filename: &str, ctx: &EmitContext) {
+        if ctx.levels > 1 {
+            write_hss_test_file(
+                filename,
+                ctx.message,
+                ctx.public_key,
+                ctx.tests,
+                ctx.n,
+                ctx.p,
+                ctx.height,
+                ctx.levels,
+            );
+        } else {
+            write_rust_test_file(
+                filename,
+                ctx.message,
+                ctx.public_key,
+                ctx.tests,
+                ctx.n,
+                ctx.p,
+                ctx.height,
+            );
+        }
+    }
+}
+
+/// Emits a flat, section-based known-answer-test file (one blank-line-separated
+/// record per test) in the style of ring's `test_file!` vectors, usable by any
+/// LMS implementation for cross-conformance testing.
+struct KatEmitter;
+
+impl TestEmitter for KatEmitter {
+    fn emit(&self, filename: &str, ctx: &EmitContext) {
+        let mut file = std::fs::File::create(filename).unwrap();
+        let public_key = to_hex(ctx.public_key);
+        let message = to_hex(ctx.message);
+        for test in ctx.tests {
+            let record = format!(
+                "Algorithm = {}\nN = {}\nW = {}\nTreeHeight = {}\nP = {}\nLevels = {}\nPublicKey = {}\nMessage = {}\nSignature = {}\nResult = {}\n\n",
+                ctx.algorithm,
+                ctx.n,
+                ctx.w,
+                ctx.height,
+                ctx.p,
+                ctx.levels,
+                public_key,
+                message,
+                to_hex(&test.signature),
+                if test.test_passed { "PASS" } else { "FAIL" },
+            );
+            file.write_all(record.as_bytes()).unwrap();
+        }
+    }
+}
+
+fn write_rust_test_file(
     filename: &str,
     message: &[u8],
     public_key: &[u8],
@@ -130,6 +589,10 @@ fn write_test_file(
     let buf = format!("\tconst TESTS: [LmsTest; {}] = [\n", tests.len());
     file.write_all(buf.as_bytes()).unwrap();
     for test in tests {
+        if !test.label.is_empty() {
+            let buf = format!("\t\t// {}\n", test.label);
+            file.write_all(buf.as_bytes()).unwrap();
+        }
         let buf = format!(
             "\t\tLmsTest{{ test_passed: {}, signature: &{:?}}},\n",
             test.test_passed, test.signature
@@ -154,6 +617,72 @@ fn write_test_file(
     file.write_all(BOILER_PLATE2.as_bytes()).unwrap();
 }
 
+fn write_hss_test_file(
+    filename: &str,
+    message: &[u8],
+    public_key: &[u8],
+    tests: &[LmsTest],
+    n: u8,
+    p: u16,
+    height: u8,
+    levels: u8,
+) {
+    let mut file = std::fs::File::create(filename).unwrap();
+    file.write_all(HSS_BOILERPLATE_1.as_bytes()).unwrap();
+
+    let buf = format!("\tconst MESSAGE :[u8; {}] = {:?};\n", message.len(), message);
+    file.write_all(buf.as_bytes()).unwrap();
+
+    let buf = format!(
+        "\tconst PUBLIC_KEY_BYTES: [u8; {}] = {:?};\n",
+        public_key.len(),
+        public_key
+    );
+    file.write_all(buf.as_bytes()).unwrap();
+
+    let buf = format!(
+        "\tlet (head, thing1, _tail): (&[u8], &[HssPublicKey<{}, {}>], &[u8]) = unsafe {{ PUBLIC_KEY_BYTES.align_to::<HssPublicKey<{}, {}>>() }};\n",
+        n / 4,
+        levels,
+        n / 4,
+        levels
+    );
+    file.write_all(buf.as_bytes()).unwrap();
+    let buf = "\tassert!(head.is_empty());
+    \tlet hss_public_key = thing1[0];\n";
+    file.write_all(buf.as_bytes()).unwrap();
+
+    let buf = format!("\tconst TESTS: [HssTest; {}] = [\n", tests.len());
+    file.write_all(buf.as_bytes()).unwrap();
+    for test in tests {
+        if !test.label.is_empty() {
+            let buf = format!("\t\t// {}\n", test.label);
+            file.write_all(buf.as_bytes()).unwrap();
+        }
+        let buf = format!(
+            "\t\tHssTest{{ test_passed: {}, signature: &{:?}}},\n",
+            test.test_passed, test.signature
+        );
+        file.write_all(buf.as_bytes()).unwrap();
+    }
+    file.write_all(b"\t];\n").unwrap();
+
+    let buf = format!(
+        "\tfor t in TESTS {{\n        let (head, thing2, _tail): (&[u8], &[HssSignature<{}, {}, {}, {}>], &[u8]) =\n            unsafe {{ t.signature.align_to::<HssSignature<{}, {}, {}, {}>>() }};\n",
+        n / 4,
+        p,
+        height,
+        levels,
+        n / 4,
+        p,
+        height,
+        levels
+    );
+    file.write_all(buf.as_bytes()).unwrap();
+
+    file.write_all(HSS_BOILER_PLATE2.as_bytes()).unwrap();
+}
+
 fn main() {
     let args = Args::parse();
     let valid_height = matches!(args.tree_height, 5 | 10 | 15 | 20);
@@ -177,6 +706,14 @@ fn main() {
         return;
     }
 
+    if args.levels < 1 || args.levels > 8 {
+        println!(
+            "Invalid number of levels: {} expected a number between 1 and 8",
+            args.levels
+        );
+        return;
+    }
+
     if args.tests < 1 || args.tests > 16 {
         println!(
             "Invalid number of tests: {} expected a number between 1 and 16",
@@ -253,17 +790,64 @@ fn main() {
         );
         return;
     }
+    let seed = match &args.seed {
+        Some(s) => {
+            let parsed = if let Some(hex) = s.strip_prefix("0x") {
+                u64::from_str_radix(hex, 16)
+            } else {
+                s.parse::<u64>()
+            };
+            match parsed {
+                Ok(seed) => seed,
+                Err(_) => {
+                    println!("Invalid seed: {} expected a decimal or 0x-hex u64", s);
+                    return;
+                }
+            }
+        }
+        None => rand::random::<u64>(),
+    };
+    println!(
+        "leaf selection seeded with 0x{:016x}; note: key material is drawn by lms_hss and is NOT reproducible",
+        seed
+    );
+    let mut rng = StdRng::seed_from_u64(seed);
+
     let message = "this is the message I want signed".as_bytes();
     let serial_public_key;
     let candidate_keys: Vec<u32> = (0..max_keys).collect();
     let chosen_qs: Vec<u32> = candidate_keys
-        .choose_multiple(&mut rand::thread_rng(), args.tests as usize)
+        .choose_multiple(&mut rng, args.tests as usize)
         .cloned()
         .collect();
     println!("going to use the following keys: {:?}", chosen_qs);
 
+    // The cross-check oracle only exists for the single-tree N=32 path below. Refuse
+    // to emit rather than silently skip verification whenever it can't actually run.
+    if args.cross_check && (args.levels > 1 || args.n != 32) {
+        eprintln!(
+            "--cross-check has no independent oracle for n={} levels={}; refusing to emit unverified vectors",
+            args.n, args.levels
+        );
+        std::process::exit(1);
+    }
+
     let mut lms_tests = vec![];
-    if args.n == 32 {
+    if args.levels > 1 {
+        let (hss_public_key, signatures) = if args.n == 32 {
+            build_hss_vectors::<32>(args.levels, &the_lms_type, &the_ots_type, message, &chosen_qs)
+        } else {
+            build_hss_vectors::<24>(args.levels, &the_lms_type, &the_ots_type, message, &chosen_qs)
+        };
+        serial_public_key = hss_public_key;
+        for signature in signatures {
+            lms_tests.push(LmsTest {
+                test_passed: true,
+                signature,
+                label: String::new(),
+            });
+        }
+    } else if args.n == 32 {
         let (lms_public_key, lms_tree) =
             lms_hss::create_lms_tree::<32>(&the_lms_type, &the_ots_type).unwrap();
         serial_public_key = serialize_public_key(&lms_public_key);
@@ -280,9 +864,39 @@ fn main() {
             )
             .unwrap();
             let serial_sig = lms_hss::serialize_signature(&lms_sig);
+            if args.cross_check {
+                let ours = lms_hss::verify_lms_signature(message, &lms_public_key, &lms_sig)
+                    .unwrap_or(false);
+                let theirs = rustcrypto_cross_check(
+                    &the_lms_type,
+                    &the_ots_type,
+                    &serial_public_key,
+                    message,
+                    &serial_sig,
+                );
+                match theirs {
+                    Some(theirs) if ours && theirs => {}
+                    Some(theirs) => {
+                        eprintln!(
+                            "cross-check FAILED for q={}: lms_hss={} rustcrypto={}",
+                            the_q_to_use, ours, theirs
+                        );
+                        std::process::exit(1);
+                    }
+                    None => {
+                        eprintln!(
+                            "cross-check requested but no RustCrypto oracle for {:?}/{:?} \
+                             (is this build compiled with --features cross-check?)",
+                            the_lms_type, the_ots_type
+                        );
+                        std::process::exit(1);
+                    }
+                }
+            }
             let test = LmsTest {
                 test_passed: true,
                 signature: serial_sig.clone(),
+                label: String::new(),
             };
             lms_tests.push(test);
         }
@@ -308,18 +922,53 @@ fn main() {
             let test = LmsTest {
                 test_passed: true,
                 signature: serial_sig.clone(),
+                label: String::new(),
             };
             lms_tests.push(test);
         }
     }
     let params = get_lmots_parameters(&the_ots_type).unwrap();
-    write_test_file(
-        &args.filename,
+
+    let num_positive = lms_tests.len();
+    let num_negative = match (args.negative_tests, args.negative_ratio) {
+        (0, Some(ratio)) => (ratio * num_positive as f64).round() as usize,
+        (n, _) => n as usize,
+    };
+    if num_negative > 0 && args.levels > 1 {
+        // `corrupt_signature` only understands the single-tree LMS byte layout; its
+        // offsets would land in the HSS `Nspk`/signed-pubkey prefix and mislabel every
+        // vector. Until HSS-aware mutation exists, negatives are single-tree only.
+        println!("skipping {} negative vectors: not supported with --levels > 1", num_negative);
+    } else if num_negative > 0 {
+        let negatives = generate_negative_tests(
+            &lms_tests,
+            num_negative,
+            args.n as usize,
+            params.p as usize,
+            args.tree_height as usize,
+        );
+        println!(
+            "adding {} negative test vectors alongside {} positive ones",
+            negatives.len(),
+            num_positive
+        );
+        lms_tests.extend(negatives);
+    }
+
+    let ctx = EmitContext {
         message,
-        &serial_public_key,
-        &lms_tests,
-        args.n,
-        params.p,
-        args.tree_height,
-    );
+        public_key: &serial_public_key,
+        tests: &lms_tests,
+        n: args.n,
+        w: args.w,
+        p: params.p,
+        height: args.tree_height,
+        levels: args.levels,
+        algorithm: &format!("{:?} / {:?}", the_lms_type, the_ots_type),
+    };
+    let emitter: Box<dyn TestEmitter> = match args.format {
+        Format::Rust => Box::new(RustEmitter),
+        Format::Kat => Box::new(KatEmitter),
+    };
+    emitter.emit(&args.filename, &ctx);
 }